@@ -0,0 +1,389 @@
+//! Conversion of data elements to and from the DICOM JSON Model,
+//! as described by [PS3.18 Annex F][1].
+//!
+//! Each data element is rendered as an object holding its VR
+//! under `"vr"` and a `"Value"` array (or, for large binary elements,
+//! an `"InlineBinary"` base64 string or a `"BulkDataURI"`).
+//! `PN` values use the `{"Alphabetic": "..."}` grouping.
+//! A full dataset is an object mapping each tag
+//! (as an 8 hex digit string) to its rendered element.
+//! [`dataset_to_json`]/[`dataset_from_json`] build and read that outer
+//! object; nested sequences are expected to recurse through the same
+//! pair of functions.
+//!
+//! [1]: https://dicom.nema.org/medical/dicom/current/output/chtml/part18/sect_F.2.html
+
+use serde_json::{json, Map, Value};
+
+use crate::DicomValue;
+use crate::VR;
+
+/// The threshold, in bytes, above which a binary element's value
+/// is referenced by `BulkDataURI` instead of being inlined as base64.
+pub const BULK_DATA_THRESHOLD: usize = 64 * 1024;
+
+/// Render a single data element as its DICOM JSON Model representation.
+///
+/// `bulk_data_uri` is consulted for binary VRs (`OB`, `OW`, `UN`, ...)
+/// whose value exceeds [`BULK_DATA_THRESHOLD`]: if it returns `Some(uri)`,
+/// the element is rendered with `"BulkDataURI"` instead of `"InlineBinary"`.
+pub fn element_to_json(
+    vr: VR,
+    value: &DicomValue,
+    mut bulk_data_uri: impl FnMut(VR, &DicomValue) -> Option<String>,
+) -> Value {
+    match vr {
+        VR::SQ => {
+            let items: Vec<Value> = value
+                .items()
+                .unwrap_or(&[])
+                .iter()
+                .map(|item| {
+                    dataset_to_json(
+                        item.iter().map(|(tag, vr, value)| (*tag, *vr, value)),
+                        &mut bulk_data_uri,
+                    )
+                })
+                .collect();
+            json!({ "vr": vr_code(vr), "Value": items })
+        }
+        VR::OB | VR::OW | VR::UN | VR::OD | VR::OF | VR::OL | VR::OV => {
+            let bytes = value.to_bytes();
+            if bytes.len() > BULK_DATA_THRESHOLD {
+                if let Some(uri) = bulk_data_uri(vr, value) {
+                    return json!({ "vr": vr_code(vr), "BulkDataURI": uri });
+                }
+            }
+            json!({
+                "vr": vr_code(vr),
+                "InlineBinary": base64::encode(&*bytes),
+            })
+        }
+        VR::PN => {
+            let names: Vec<Value> = value
+                .to_multi_str()
+                .iter()
+                .filter(|s| !s.is_empty())
+                .map(|name| json!({ "Alphabetic": name }))
+                .collect();
+            json!({ "vr": vr_code(vr), "Value": names })
+        }
+        VR::IS | VR::DS | VR::US | VR::SS | VR::UL | VR::SL | VR::FL | VR::FD | VR::UV
+        | VR::SV => {
+            let values: Vec<Value> = value
+                .to_multi_str()
+                .iter()
+                .filter(|s| !s.is_empty())
+                .map(|s| numeric_value(s))
+                .collect();
+            json!({ "vr": vr_code(vr), "Value": values })
+        }
+        _ => {
+            let values: Vec<Value> = value
+                .to_multi_str()
+                .iter()
+                .filter(|s| !s.is_empty())
+                .map(|s| json!(s))
+                .collect();
+            json!({ "vr": vr_code(vr), "Value": values })
+        }
+    }
+}
+
+/// Render an entire dataset as a DICOM JSON Model object,
+/// keyed by the 8 hex digit uppercase representation of each tag.
+pub fn dataset_to_json<'a>(
+    elements: impl IntoIterator<Item = ((u16, u16), VR, &'a DicomValue)>,
+    mut bulk_data_uri: impl FnMut(VR, &DicomValue) -> Option<String>,
+) -> Value {
+    let mut map = Map::new();
+    for ((group, element), vr, value) in elements {
+        let key = format!("{:04X}{:04X}", group, element);
+        map.insert(key, element_to_json(vr, value, &mut bulk_data_uri));
+    }
+    Value::Object(map)
+}
+
+/// Parse a single DICOM JSON Model element back into its VR and value.
+///
+/// `bulk_data` is consulted whenever the element carries a `"BulkDataURI"`
+/// instead of an inline value, and should resolve it to the referenced bytes.
+pub fn element_from_json(
+    json: &Value,
+    mut bulk_data: impl FnMut(&str) -> Option<Vec<u8>>,
+) -> Result<(VR, DicomValue), String> {
+    let vr = json
+        .get("vr")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "element is missing \"vr\"".to_string())?;
+    let vr = vr_from_code(vr)?;
+
+    if let Some(uri) = json.get("BulkDataURI").and_then(Value::as_str) {
+        let bytes = bulk_data(uri)
+            .ok_or_else(|| format!("unresolved BulkDataURI {:?}", uri))?;
+        return Ok((vr, DicomValue::from(bytes)));
+    }
+
+    match vr {
+        VR::SQ => {
+            let items = json
+                .get("Value")
+                .and_then(Value::as_array)
+                .ok_or_else(|| "SQ element is missing \"Value\"".to_string())?;
+            let items = items
+                .iter()
+                .map(|item| dataset_from_json(item, &mut bulk_data))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((vr, DicomValue::from_items(items)))
+        }
+        VR::OB | VR::OW | VR::UN | VR::OD | VR::OF | VR::OL | VR::OV => {
+            let inline = json
+                .get("InlineBinary")
+                .and_then(Value::as_str)
+                .ok_or_else(|| "binary element is missing \"InlineBinary\"".to_string())?;
+            let bytes = base64::decode(inline).map_err(|e| e.to_string())?;
+            Ok((vr, DicomValue::from(bytes)))
+        }
+        VR::PN => {
+            let names: Vec<String> = json
+                .get("Value")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.get("Alphabetic").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect();
+            Ok((vr, DicomValue::from(names)))
+        }
+        _ => {
+            let values: Vec<String> = json
+                .get("Value")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(|v| match v {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect();
+            Ok((vr, DicomValue::from(values)))
+        }
+    }
+}
+
+/// Parse a full DICOM JSON Model dataset back into `(tag, vr, value)` triples,
+/// keyed by the 8 hex digit tag each element was stored under.
+///
+/// `bulk_data` is consulted the same way as in [`element_from_json`].
+pub fn dataset_from_json(
+    json: &Value,
+    mut bulk_data: impl FnMut(&str) -> Option<Vec<u8>>,
+) -> Result<Vec<((u16, u16), VR, DicomValue)>, String> {
+    let map = json
+        .as_object()
+        .ok_or_else(|| "dataset must be a JSON object".to_string())?;
+
+    map.iter()
+        .map(|(tag, element)| {
+            if tag.len() != 8 {
+                return Err(format!("invalid tag {:?}", tag));
+            }
+            let group = u16::from_str_radix(&tag[0..4], 16).map_err(|e| e.to_string())?;
+            let elem = u16::from_str_radix(&tag[4..8], 16).map_err(|e| e.to_string())?;
+            let (vr, value) = element_from_json(element, &mut bulk_data)?;
+            Ok(((group, elem), vr, value))
+        })
+        .collect()
+}
+
+/// Parse a numeric element string representation into a JSON number,
+/// falling back to a JSON string if it cannot be parsed
+/// (this should not normally happen for well-formed numeric VRs).
+fn numeric_value(s: &str) -> Value {
+    let s = s.trim();
+    if let Ok(i) = s.parse::<i64>() {
+        json!(i)
+    } else if let Ok(f) = s.parse::<f64>() {
+        json!(f)
+    } else {
+        json!(s)
+    }
+}
+
+/// The two-letter VR code as used by the DICOM JSON Model's `"vr"` field.
+fn vr_code(vr: VR) -> &'static str {
+    match vr {
+        VR::AE => "AE",
+        VR::AS => "AS",
+        VR::AT => "AT",
+        VR::CS => "CS",
+        VR::DA => "DA",
+        VR::DS => "DS",
+        VR::DT => "DT",
+        VR::FL => "FL",
+        VR::FD => "FD",
+        VR::IS => "IS",
+        VR::LO => "LO",
+        VR::LT => "LT",
+        VR::OB => "OB",
+        VR::OD => "OD",
+        VR::OF => "OF",
+        VR::OL => "OL",
+        VR::OV => "OV",
+        VR::OW => "OW",
+        VR::PN => "PN",
+        VR::SH => "SH",
+        VR::SL => "SL",
+        VR::SQ => "SQ",
+        VR::SS => "SS",
+        VR::ST => "ST",
+        VR::SV => "SV",
+        VR::TM => "TM",
+        VR::UC => "UC",
+        VR::UI => "UI",
+        VR::UL => "UL",
+        VR::UN => "UN",
+        VR::UR => "UR",
+        VR::US => "US",
+        VR::UT => "UT",
+        VR::UV => "UV",
+    }
+}
+
+/// Parse a two-letter VR code as used by the DICOM JSON Model's `"vr"` field.
+fn vr_from_code(code: &str) -> Result<VR, String> {
+    Ok(match code {
+        "AE" => VR::AE,
+        "AS" => VR::AS,
+        "AT" => VR::AT,
+        "CS" => VR::CS,
+        "DA" => VR::DA,
+        "DS" => VR::DS,
+        "DT" => VR::DT,
+        "FL" => VR::FL,
+        "FD" => VR::FD,
+        "IS" => VR::IS,
+        "LO" => VR::LO,
+        "LT" => VR::LT,
+        "OB" => VR::OB,
+        "OD" => VR::OD,
+        "OF" => VR::OF,
+        "OL" => VR::OL,
+        "OV" => VR::OV,
+        "OW" => VR::OW,
+        "PN" => VR::PN,
+        "SH" => VR::SH,
+        "SL" => VR::SL,
+        "SQ" => VR::SQ,
+        "SS" => VR::SS,
+        "ST" => VR::ST,
+        "SV" => VR::SV,
+        "TM" => VR::TM,
+        "UC" => VR::UC,
+        "UI" => VR::UI,
+        "UL" => VR::UL,
+        "UN" => VR::UN,
+        "UR" => VR::UR,
+        "US" => VR::US,
+        "UT" => VR::UT,
+        "UV" => VR::UV,
+        other => return Err(format!("unrecognized VR {:?}", other)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_string_value() {
+        let value = DicomValue::from("DOE");
+        let json = element_to_json(VR::CS, &value, |_, _| None);
+        assert_eq!(json["vr"], "CS");
+        assert_eq!(json["Value"][0], "DOE");
+    }
+
+    #[test]
+    fn renders_numeric_value() {
+        let value = DicomValue::from(512_u16);
+        let json = element_to_json(VR::US, &value, |_, _| None);
+        assert_eq!(json["vr"], "US");
+        assert_eq!(json["Value"][0], 512);
+    }
+
+    #[test]
+    fn renders_person_name() {
+        let value = DicomValue::from("Doe^John");
+        let json = element_to_json(VR::PN, &value, |_, _| None);
+        assert_eq!(json["Value"][0]["Alphabetic"], "Doe^John");
+    }
+
+    #[test]
+    fn renders_inline_binary() {
+        let value = DicomValue::from(&[0xDE_u8, 0xAD, 0xBE, 0xEF][..]);
+        let json = element_to_json(VR::OB, &value, |_, _| None);
+        assert_eq!(json["InlineBinary"], base64::encode([0xDE, 0xAD, 0xBE, 0xEF]));
+    }
+
+    #[test]
+    fn round_trips_string_value() {
+        let value = DicomValue::from("DOE");
+        let json = element_to_json(VR::CS, &value, |_, _| None);
+        let (vr, parsed) = element_from_json(&json, |_| None).unwrap();
+        assert_eq!(vr, VR::CS);
+        assert_eq!(parsed.to_multi_str(), value.to_multi_str());
+    }
+
+    #[test]
+    fn round_trips_person_name() {
+        let value = DicomValue::from("Doe^John");
+        let json = element_to_json(VR::PN, &value, |_, _| None);
+        let (vr, parsed) = element_from_json(&json, |_| None).unwrap();
+        assert_eq!(vr, VR::PN);
+        assert_eq!(parsed.to_multi_str(), value.to_multi_str());
+    }
+
+    #[test]
+    fn round_trips_inline_binary() {
+        let value = DicomValue::from(&[0xDE_u8, 0xAD, 0xBE, 0xEF][..]);
+        let json = element_to_json(VR::OB, &value, |_, _| None);
+        let (vr, parsed) = element_from_json(&json, |_| None).unwrap();
+        assert_eq!(vr, VR::OB);
+        assert_eq!(parsed.to_bytes(), value.to_bytes());
+    }
+
+    #[test]
+    fn round_trips_dataset() {
+        let value = DicomValue::from("DOE");
+        let json = dataset_to_json([((0x0010, 0x0010), VR::PN, &value)], |_, _| None);
+        let elements = dataset_from_json(&json, |_| None).unwrap();
+        assert_eq!(elements.len(), 1);
+        assert_eq!(elements[0].0, (0x0010, 0x0010));
+        assert_eq!(elements[0].1, VR::PN);
+    }
+
+    #[test]
+    fn renders_sequence() {
+        let name = DicomValue::from("Doe^John");
+        let item = vec![((0x0010, 0x0010), VR::PN, name)];
+        let value = DicomValue::from_items(vec![item]);
+        let json = element_to_json(VR::SQ, &value, |_, _| None);
+        assert_eq!(json["vr"], "SQ");
+        assert_eq!(json["Value"][0]["00100010"]["Value"][0]["Alphabetic"], "Doe^John");
+    }
+
+    #[test]
+    fn round_trips_sequence() {
+        let name = DicomValue::from("Doe^John");
+        let item = vec![((0x0010, 0x0010), VR::PN, name)];
+        let value = DicomValue::from_items(vec![item]);
+        let json = element_to_json(VR::SQ, &value, |_, _| None);
+        let (vr, parsed) = element_from_json(&json, |_| None).unwrap();
+        assert_eq!(vr, VR::SQ);
+        let items = parsed.items().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].len(), 1);
+        assert_eq!(items[0][0].0, (0x0010, 0x0010));
+        assert_eq!(items[0][0].1, VR::PN);
+    }
+}
@@ -28,15 +28,19 @@ extern crate encoding;
 extern crate quick_error;
 extern crate chrono;
 extern crate itertools;
+extern crate base64;
+extern crate serde_json;
 
 pub mod data;
 pub mod dictionary;
 pub mod error;
 pub mod file;
 pub mod iterator;
+pub mod json;
 pub mod loader;
 pub mod meta;
 pub mod object;
+pub mod rle;
 pub mod transfer_syntax;
 
 pub use data::value::DicomValue;
@@ -0,0 +1,236 @@
+//! RLE Lossless encoding and decoding of pixel data,
+//! as described by [PS3.5 Annex G][1].
+//!
+//! A single frame is split into one or more *segments*
+//! (one per 8-bit sample plane, most-significant byte first for 16-bit
+//! samples, one plane per color channel for color-by-plane images),
+//! each of which is compressed independently with a PackBits-style
+//! byte-oriented run-length scheme.
+//! The encoded frame is prefixed with a 64-byte header
+//! holding the segment count and the byte offset of each segment.
+//!
+//! [1]: https://dicom.nema.org/medical/dicom/current/output/chtml/part05/sect_G.3.html
+
+/// The size in bytes of an RLE frame header.
+const HEADER_LEN: usize = 64;
+
+/// The maximum number of segments a single frame header can describe.
+const MAX_SEGMENTS: usize = 15;
+
+/// Encode a sequence of frames as RLE Lossless fragments.
+///
+/// `frames` holds the raw, interleaved pixel data of each frame.
+/// `samples_per_pixel` and `bits_allocated` determine how each frame
+/// is split into segments: one segment per 8 bits of each sample,
+/// in color-by-plane order.
+///
+/// Returns one encoded fragment (including its 64-byte header) per frame.
+pub fn encode_rle(
+    frames: &[Vec<u8>],
+    rows: u16,
+    cols: u16,
+    samples_per_pixel: u16,
+    bits_allocated: u16,
+) -> Vec<Vec<u8>> {
+    let bytes_per_sample = (bits_allocated / 8) as usize;
+    let num_segments = samples_per_pixel as usize * bytes_per_sample;
+    debug_assert!(num_segments <= MAX_SEGMENTS);
+    let pixel_count = rows as usize * cols as usize;
+
+    frames
+        .iter()
+        .map(|frame| encode_frame(frame, pixel_count, samples_per_pixel as usize, bytes_per_sample, num_segments))
+        .collect()
+}
+
+/// Split a single frame into its constituent segments and PackBits-encode
+/// each of them, prefixing the result with the 64-byte frame header.
+fn encode_frame(
+    frame: &[u8],
+    pixel_count: usize,
+    samples_per_pixel: usize,
+    bytes_per_sample: usize,
+    num_segments: usize,
+) -> Vec<u8> {
+    let segments = split_into_segments(frame, pixel_count, samples_per_pixel, bytes_per_sample);
+    debug_assert_eq!(segments.len(), num_segments);
+
+    let encoded: Vec<Vec<u8>> = segments.iter().map(|s| pack_bits_encode(s)).collect();
+
+    let mut out = vec![0u8; HEADER_LEN];
+    out[0..4].copy_from_slice(&(encoded.len() as u32).to_le_bytes());
+    let mut offset = HEADER_LEN as u32;
+    for (i, segment) in encoded.iter().enumerate() {
+        let header_offset = 4 + i * 4;
+        out[header_offset..header_offset + 4].copy_from_slice(&offset.to_le_bytes());
+        offset += segment.len() as u32;
+    }
+    for segment in &encoded {
+        out.extend_from_slice(segment);
+    }
+    out
+}
+
+/// Split interleaved pixel data into per-sample-byte segments,
+/// in color-by-plane order (all of one byte plane before the next).
+fn split_into_segments(
+    frame: &[u8],
+    pixel_count: usize,
+    samples_per_pixel: usize,
+    bytes_per_sample: usize,
+) -> Vec<Vec<u8>> {
+    let mut segments = vec![Vec::with_capacity(pixel_count); samples_per_pixel * bytes_per_sample];
+    let sample_stride = samples_per_pixel * bytes_per_sample;
+
+    for pixel in 0..pixel_count {
+        let pixel_start = pixel * sample_stride;
+        for sample in 0..samples_per_pixel {
+            let sample_start = pixel_start + sample * bytes_per_sample;
+            // most-significant byte first
+            for byte_index in 0..bytes_per_sample {
+                let byte = frame[sample_start + (bytes_per_sample - 1 - byte_index)];
+                segments[sample * bytes_per_sample + byte_index].push(byte);
+            }
+        }
+    }
+    segments
+}
+
+/// PackBits-encode a single segment, padding the result to an even length.
+fn pack_bits_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let run = data[i..].iter().take_while(|&&b| b == data[i]).count().min(128);
+        if run >= 2 {
+            out.push((257 - run) as u8);
+            out.push(data[i]);
+            i += run;
+        } else {
+            let start = i;
+            let mut len = 1;
+            i += 1;
+            while i < data.len() && len < 128 {
+                let next_run = data[i..].iter().take_while(|&&b| b == data[i]).count();
+                if next_run >= 3 {
+                    break;
+                }
+                len += 1;
+                i += 1;
+            }
+            out.push((len - 1) as u8);
+            out.extend_from_slice(&data[start..start + len]);
+        }
+    }
+    if out.len() % 2 != 0 {
+        out.push(0x00);
+    }
+    out
+}
+
+/// Decode a single RLE Lossless fragment (as produced by [`encode_rle`])
+/// back into interleaved pixel data.
+pub fn decode_rle(
+    fragment: &[u8],
+    pixel_count: usize,
+    samples_per_pixel: u16,
+    bits_allocated: u16,
+) -> Vec<u8> {
+    let bytes_per_sample = (bits_allocated / 8) as usize;
+    let samples_per_pixel = samples_per_pixel as usize;
+    let num_segments = samples_per_pixel * bytes_per_sample;
+
+    let segment_count = u32::from_le_bytes(fragment[0..4].try_into().unwrap()) as usize;
+    debug_assert_eq!(segment_count, num_segments);
+
+    let mut offsets = Vec::with_capacity(segment_count);
+    for i in 0..segment_count {
+        let o = 4 + i * 4;
+        offsets.push(u32::from_le_bytes(fragment[o..o + 4].try_into().unwrap()) as usize);
+    }
+
+    let segments: Vec<Vec<u8>> = offsets
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = offsets.get(i + 1).copied().unwrap_or(fragment.len());
+            pack_bits_decode(&fragment[start..end], pixel_count)
+        })
+        .collect();
+
+    let mut out = vec![0u8; pixel_count * samples_per_pixel * bytes_per_sample];
+    let sample_stride = samples_per_pixel * bytes_per_sample;
+    for pixel in 0..pixel_count {
+        let pixel_start = pixel * sample_stride;
+        for sample in 0..samples_per_pixel {
+            let sample_start = pixel_start + sample * bytes_per_sample;
+            for byte_index in 0..bytes_per_sample {
+                let segment = &segments[sample * bytes_per_sample + byte_index];
+                out[sample_start + (bytes_per_sample - 1 - byte_index)] = segment[pixel];
+            }
+        }
+    }
+    out
+}
+
+/// PackBits-decode a single segment, truncated to `expected_len` bytes
+/// (the trailing padding byte, if any, is discarded).
+fn pack_bits_decode(data: &[u8], expected_len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(expected_len);
+    let mut i = 0;
+    while i < data.len() && out.len() < expected_len {
+        let n = data[i] as i8;
+        i += 1;
+        if n >= 0 {
+            let count = n as usize + 1;
+            out.extend_from_slice(&data[i..i + count]);
+            i += count;
+        } else if n != -128 {
+            let count = 257 - (n as i16 + 256) as usize;
+            out.extend(std::iter::repeat(data[i]).take(count));
+            i += 1;
+        }
+        // n == -128 (0x80) is a no-op/padding byte
+    }
+    out.truncate(expected_len);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_grayscale_8bit() {
+        let rows = 4u16;
+        let cols = 4u16;
+        let frame: Vec<u8> = vec![0, 0, 0, 1, 2, 2, 2, 2, 3, 4, 5, 6, 7, 7, 7, 7];
+        let encoded = encode_rle(&[frame.clone()], rows, cols, 1, 8);
+        assert_eq!(encoded.len(), 1);
+        let decoded = decode_rle(&encoded[0], rows as usize * cols as usize, 1, 8);
+        assert_eq!(decoded, frame);
+    }
+
+    #[test]
+    fn round_trip_rgb_8bit() {
+        let rows = 2u16;
+        let cols = 2u16;
+        // 4 pixels, 3 samples each, interleaved RGB
+        let frame: Vec<u8> = vec![
+            10, 20, 30, 10, 20, 30, 11, 21, 31, 12, 22, 32,
+        ];
+        let encoded = encode_rle(&[frame.clone()], rows, cols, 3, 8);
+        let decoded = decode_rle(&encoded[0], rows as usize * cols as usize, 3, 8);
+        assert_eq!(decoded, frame);
+    }
+
+    #[test]
+    fn round_trip_grayscale_16bit() {
+        let rows = 1u16;
+        let cols = 4u16;
+        let frame: Vec<u8> = vec![0x01, 0x02, 0x01, 0x02, 0xFF, 0x00, 0x12, 0x34];
+        let encoded = encode_rle(&[frame.clone()], rows, cols, 1, 16);
+        let decoded = decode_rle(&encoded[0], rows as usize * cols as usize, 1, 16);
+        assert_eq!(decoded, frame);
+    }
+}
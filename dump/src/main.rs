@@ -1,12 +1,34 @@
 //! A CLI tool for inspecting the contents of a DICOM file
-//! by printing it in a human readable format.
+//! by printing it in a human readable format,
+//! or as a DICOM JSON Model document with `--format json`.
 use dicom_object::open_file;
 use dicom_dump::{ColorMode, DumpOptions};
 use snafu::{ErrorCompat, Whatever, whatever};
-use std::io::ErrorKind;
+use std::io::{ErrorKind, Write};
 use std::path::PathBuf;
 use clap::Parser;
 
+/// Render a DICOM object as a DICOM JSON Model document (PS3.18 Annex F),
+/// built on top of the element-level helpers in `dicom_core::json`
+/// (`InMemDicomObject` itself lives in the `dicom-object` crate and does
+/// not expose `to_json` directly).
+fn object_to_json(obj: &dicom_object::InMemDicomObject) -> serde_json::Value {
+    dicom_core::json::dataset_to_json(
+        obj.iter()
+            .map(|e| ((e.tag().group(), e.tag().element()), e.vr(), e.value())),
+        |_, _| None,
+    )
+}
+
+/// The output format to print each file in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Format {
+    /// Human readable dump (default)
+    Dump,
+    /// DICOM JSON Model (PS3.18 Annex F)
+    Json,
+}
+
 /// Exit code for when an error emerged while reading the DICOM file.
 const ERROR_READ: i32 = -2;
 /// Exit code for when an error emerged while dumping the file.
@@ -43,6 +65,9 @@ struct App {
     /// The color mode
     #[clap(long = "color", default_value = "auto")]
     color: ColorMode,
+    /// The output format
+    #[clap(long = "format", value_enum, default_value_t = Format::Dump)]
+    format: Format,
     /// Fail if any errors are encountered
     #[clap(long = "fail-first")]
     fail_first: bool,
@@ -66,6 +91,7 @@ fn run() -> Result<(), Whatever> {
         no_limit,
         width,
         color,
+        format,
         fail_first,
     } = App::parse();
 
@@ -83,7 +109,9 @@ fn run() -> Result<(), Whatever> {
     let mut errors: i32 = 0;
 
     for filename in &filenames {
-        println!("{}: ", filename.display());
+        if format == Format::Dump {
+            println!("{}: ", filename.display());
+        }
         match open_file(filename) {
             Err(e) => {
                 report(e);
@@ -93,7 +121,16 @@ fn run() -> Result<(), Whatever> {
                 errors += 1;
             }
             Ok(obj) => {
-                if let Err(ref e) = options.dump_file(&obj) {
+                let result = match format {
+                    Format::Dump => options.dump_file(&obj),
+                    Format::Json => {
+                        let mut stdout = std::io::stdout();
+                        serde_json::to_writer_pretty(&mut stdout, &object_to_json(&obj))
+                            .map_err(|e| std::io::Error::new(ErrorKind::Other, e))
+                            .and_then(|_| writeln!(stdout))
+                    }
+                };
+                if let Err(ref e) = result {
                     if e.kind() == ErrorKind::BrokenPipe {
                         // handle broken pipe separately with a no-op
                     } else {
@@ -9,33 +9,493 @@
 //!
 //! The new DICOM object is saved to a new file,
 //! with the same SOP instance UID and SOP class UID as the base file,
-//! encoded in Explicit VR Little Endian.
+//! encoded in Explicit VR Little Endian by default,
+//! or in an encapsulated transfer syntax when `--transfer-syntax` is given.
+//! The image (or, with `--doc`, an encapsulated document)
+//! may be given as a filesystem path or as a `data:` URI.
+//! With `--doc`, the object is instead saved with the Encapsulated PDF
+//! Storage SOP class UID, and the Image Pixel module attributes
+//! (including _Presentation LUT Shape_) are removed.
+//!
+//! If no base file is given (`--dcm-file` is omitted), a new
+//! Secondary Capture Image Storage object is synthesized from scratch
+//! instead of cloning one, with fresh UIDs, the current date and time,
+//! and the patient name, patient ID and modality taken from the
+//! `--patient-name`, `--patient-id` and `--modality` flags
+//! (see [`new_secondary_capture_object`]).
+//!
+//! With `--auto-window`, the replacement pixel data is scanned to derive
+//! VOI windowing and pixel value range attributes for MONOCHROME images
+//! (see [`pixel_value_range`]).
 //!
 //! [1]: https://dicom.nema.org/medical/dicom/current/output/chtml/part03/sect_C.7.6.3.html
+use std::io::Cursor;
 use std::path::PathBuf;
 
-use dicom_core::{value::PrimitiveValue, DataElement, VR};
+use chrono::Local;
+use dicom_core::{
+    rle::encode_rle,
+    value::{PixelFragmentSequence, PrimitiveValue},
+    DataElement, VR,
+};
 use dicom_dictionary_std::tags;
-use dicom_object::{open_file, FileMetaTableBuilder};
+use dicom_object::{open_file, FileMetaTableBuilder, InMemDicomObject};
+use image::{codecs::jpeg::JpegEncoder, AnimationDecoder, DynamicImage};
 use snafu::ErrorCompat;
 use clap::Parser;
 
+/// SOP Class UID of Secondary Capture Image Storage,
+/// used for objects synthesized from scratch (no `--dcm-file`).
+const SECONDARY_CAPTURE_SOP_CLASS_UID: &str = "1.2.840.10008.5.1.4.1.1.7";
+
+/// SOP Class UID of Encapsulated PDF Storage, used for `--doc` output.
+const ENCAPSULATED_PDF_SOP_CLASS_UID: &str = "1.2.840.10008.5.1.4.1.1.104.1";
+
+/// The transfer syntax to encode the output pixel data with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Codec {
+    /// Explicit VR Little Endian, pixel data stored natively (default)
+    Native,
+    /// JPEG Baseline (Process 1)
+    JpegBaseline,
+    /// JPEG 2000 (Lossless Only);
+    /// encoding is not supported, so selecting it fails with an error
+    Jpeg2000,
+    /// RLE Lossless
+    Rle,
+}
+
+impl Codec {
+    /// The transfer syntax UID to write to the file meta table
+    /// when this codec is used.
+    fn transfer_syntax_uid(self) -> &'static str {
+        match self {
+            Codec::Native => "1.2.840.10008.1.2.1",
+            Codec::JpegBaseline => "1.2.840.10008.1.2.4.50",
+            Codec::Jpeg2000 => "1.2.840.10008.1.2.4.90",
+            Codec::Rle => "1.2.840.10008.1.2.5",
+        }
+    }
+
+    /// Override the Photometric Interpretation this codec requires for a
+    /// color image, if any (e.g. JPEG Baseline always stores YBR, never RGB).
+    /// Grayscale images (`MONOCHROME2`) are unaffected by any codec.
+    fn photometric_interpretation_override(self, samples_per_pixel: u16) -> Option<&'static str> {
+        if samples_per_pixel == 1 {
+            return None;
+        }
+        match self {
+            // `image`'s JPEG encoder writes all three YCbCr components at
+            // full resolution (it has no chroma subsampling support), so the
+            // bitstream is `YBR_FULL` (PS3.3 C.7.6.3.1.2), not the `_422`
+            // subsampled variant.
+            Codec::JpegBaseline => Some("YBR_FULL"),
+            Codec::Native | Codec::Jpeg2000 | Codec::Rle => None,
+        }
+    }
+}
+
 /// Convert and replace a DICOM file's image with another image
 #[derive(Debug, Parser)]
 struct App {
     /// Path to the base DICOM file to read
-    dcm_file: PathBuf,
-    /// Path to the image file to replace the DICOM file
-    img_file: PathBuf,
+    /// (if omitted, a new Secondary Capture Image Storage object
+    /// is synthesized from scratch, see `--patient-name`/`--patient-id`/`--modality`)
+    #[clap(short = 'd', long = "dcm-file")]
+    dcm_file: Option<PathBuf>,
+    /// Path, or `data:<mediatype>;base64,<data>` URI,
+    /// to the image file to replace the DICOM file's pixel data
+    #[clap(required_unless_present = "doc")]
+    img_file: Option<String>,
+    /// Path, or `data:<mediatype>;base64,<data>` URI,
+    /// to an encapsulated document (e.g. `application/pdf`)
+    /// to store instead of image pixel data
+    #[clap(long = "doc", conflicts_with = "img_file")]
+    doc: Option<String>,
     /// Path to the output image
     /// (default is to replace input extension with `.new.dcm`)
     #[clap(short = 'o', long = "out")]
     output: Option<PathBuf>,
+    /// The transfer syntax to encode the pixel data with
+    /// (default is to store pixels natively in Explicit VR Little Endian)
+    #[clap(short = 't', long = "transfer-syntax", value_enum, default_value_t = Codec::Native)]
+    transfer_syntax: Codec,
+    /// Patient's name, used when synthesizing an object from scratch
+    /// (ignored when `--dcm-file` is given)
+    #[clap(long = "patient-name", default_value = "Anonymous")]
+    patient_name: String,
+    /// Patient ID, used when synthesizing an object from scratch
+    /// (ignored when `--dcm-file` is given)
+    #[clap(long = "patient-id", default_value = "")]
+    patient_id: String,
+    /// Modality, used when synthesizing an object from scratch
+    /// (ignored when `--dcm-file` is given)
+    #[clap(long = "modality", default_value = "OT")]
+    modality: String,
+    /// Scan the replacement pixel data to derive VOI windowing
+    /// (`WindowCenter`/`WindowWidth`) and pixel value range
+    /// (`SmallestImagePixelValue`/`LargestImagePixelValue`) attributes
+    /// (MONOCHROME images only; has no effect on RGB)
+    #[clap(long = "auto-window")]
+    auto_window: bool,
     /// Print more information about the image and the output file
     #[clap(short = 'v', long = "verbose")]
     verbose: bool,
 }
 
+/// Compute the minimum and maximum sample value across every frame,
+/// used by `--auto-window` to derive VOI windowing and pixel value
+/// range attributes. Samples are read as 8- or 16-bit unsigned integers
+/// (little endian) according to `bits_stored`.
+fn pixel_value_range(frames: &[Frame], bits_stored: u16) -> (i64, i64) {
+    let mut min = i64::MAX;
+    let mut max = i64::MIN;
+    for frame in frames {
+        if bits_stored == 8 {
+            for &sample in &frame.data {
+                min = min.min(sample as i64);
+                max = max.max(sample as i64);
+            }
+        } else {
+            for sample in frame.data.chunks_exact(2) {
+                let sample = u16::from_le_bytes([sample[0], sample[1]]) as i64;
+                min = min.min(sample);
+                max = max.max(sample);
+            }
+        }
+    }
+    (min, max)
+}
+
+/// Generate a fresh DICOM UID using the UUID-derived `2.25` root
+/// described by PS3.5 Annex B.2.
+fn new_uid() -> String {
+    format!("2.25.{}", rand::random::<u128>())
+}
+
+/// Build a minimal valid Secondary Capture Image Storage object from
+/// scratch (PS3.3 Annex A.8), generating fresh Study/Series/SOP Instance
+/// UIDs and filling in the mandatory Patient/Study/Series/General Image
+/// attributes. The Image Pixel module itself is populated later, once
+/// the source image has been decoded.
+fn new_secondary_capture_object(
+    patient_name: &str,
+    patient_id: &str,
+    modality: &str,
+) -> InMemDicomObject {
+    let now = Local::now();
+    let date = now.format("%Y%m%d").to_string();
+    let time = now.format("%H%M%S").to_string();
+
+    let mut obj = InMemDicomObject::create_empty();
+
+    obj.put(DataElement::new(
+        tags::SOP_CLASS_UID,
+        VR::UI,
+        PrimitiveValue::from(SECONDARY_CAPTURE_SOP_CLASS_UID),
+    ));
+    obj.put(DataElement::new(
+        tags::SOP_INSTANCE_UID,
+        VR::UI,
+        PrimitiveValue::from(new_uid()),
+    ));
+    obj.put(DataElement::new(
+        tags::STUDY_INSTANCE_UID,
+        VR::UI,
+        PrimitiveValue::from(new_uid()),
+    ));
+    obj.put(DataElement::new(
+        tags::SERIES_INSTANCE_UID,
+        VR::UI,
+        PrimitiveValue::from(new_uid()),
+    ));
+
+    obj.put(DataElement::new(
+        tags::PATIENT_NAME,
+        VR::PN,
+        PrimitiveValue::from(patient_name),
+    ));
+    obj.put(DataElement::new(
+        tags::PATIENT_ID,
+        VR::LO,
+        PrimitiveValue::from(patient_id),
+    ));
+    obj.put(DataElement::new(
+        tags::MODALITY,
+        VR::CS,
+        PrimitiveValue::from(modality),
+    ));
+
+    obj.put(DataElement::new(
+        tags::STUDY_DATE,
+        VR::DA,
+        PrimitiveValue::from(date.as_str()),
+    ));
+    obj.put(DataElement::new(
+        tags::STUDY_TIME,
+        VR::TM,
+        PrimitiveValue::from(time.as_str()),
+    ));
+    obj.put(DataElement::new(tags::SERIES_NUMBER, VR::IS, PrimitiveValue::from("1")));
+    obj.put(DataElement::new(tags::INSTANCE_NUMBER, VR::IS, PrimitiveValue::from("1")));
+
+    obj.put(DataElement::new(
+        tags::CONTENT_DATE,
+        VR::DA,
+        PrimitiveValue::from(date.as_str()),
+    ));
+    obj.put(DataElement::new(
+        tags::CONTENT_TIME,
+        VR::TM,
+        PrimitiveValue::from(time.as_str()),
+    ));
+    obj.put(DataElement::new(
+        tags::ACQUISITION_DATE,
+        VR::DA,
+        PrimitiveValue::from(date),
+    ));
+    obj.put(DataElement::new(
+        tags::ACQUISITION_TIME,
+        VR::TM,
+        PrimitiveValue::from(time),
+    ));
+
+    obj
+}
+
+/// Build the `PixelSequence` value of an encapsulated Pixel Data element
+/// (the `(7FE0,0010) OB` element, stored with an undefined length), as
+/// described by PS3.5 Annex A.4: a Basic Offset Table (one `u32` per
+/// fragment, relative to the first byte of the first fragment's item tag,
+/// so each offset counts the 8-byte item header of every earlier fragment) plus the
+/// fragments themselves. Wrapping each fragment as an item, and marking the
+/// element's length as undefined, is done by the encoder when it writes a
+/// `PixelFragmentSequence` value, so this function only has to compute the
+/// offset table — it must not hand-pack the item/delimiter framing bytes
+/// into a plain (defined-length) primitive value, since that would not be
+/// read back as encapsulated pixel data.
+fn encapsulate(fragments: Vec<Vec<u8>>) -> PixelFragmentSequence<Vec<u8>> {
+    let mut offset = 0u32;
+    let mut offset_table = Vec::with_capacity(fragments.len());
+    for fragment in &fragments {
+        offset_table.push(offset);
+        offset += 8 + fragment.len() as u32;
+    }
+    PixelFragmentSequence::new(offset_table, fragments)
+}
+
+/// Encode a single frame of raw pixel data into one compressed fragment,
+/// ready to be wrapped by [`encapsulate`].
+fn compress_frame(
+    codec: Codec,
+    pixeldata: &[u8],
+    width: u32,
+    height: u32,
+    color: image::ColorType,
+    samples_per_pixel: u16,
+    bits_allocated: u16,
+) -> Result<Vec<u8>, String> {
+    match codec {
+        Codec::Native => unreachable!("native codec does not use fragment encoding"),
+        Codec::JpegBaseline => {
+            let mut buf = Vec::new();
+            JpegEncoder::new(&mut buf)
+                .encode(pixeldata, width, height, color)
+                .map_err(|e| e.to_string())?;
+            if buf.len() % 2 != 0 {
+                buf.push(0x00);
+            }
+            Ok(buf)
+        }
+        Codec::Jpeg2000 => {
+            Err("JPEG 2000 encoding is not yet supported".to_string())
+        }
+        Codec::Rle => {
+            let height = height as u16;
+            let width = width as u16;
+            Ok(encode_rle(
+                &[pixeldata.to_vec()],
+                height,
+                width,
+                samples_per_pixel,
+                bits_allocated,
+            )
+            .remove(0))
+        }
+    }
+}
+
+/// The source of an image or document given on the command line:
+/// either a filesystem path, or inline bytes decoded from a `data:` URI.
+enum ImageSource {
+    Path(PathBuf),
+    Inline { media_type: String, bytes: Vec<u8> },
+}
+
+impl ImageSource {
+    /// Parse a `data:<mediatype>;base64,<data>` URI,
+    /// falling back to treating the argument as a filesystem path.
+    fn parse(arg: &str) -> Result<Self, String> {
+        let Some(rest) = arg.strip_prefix("data:") else {
+            return Ok(ImageSource::Path(PathBuf::from(arg)));
+        };
+        let (header, payload) = rest
+            .split_once(',')
+            .ok_or_else(|| "malformed data URI: missing ','".to_string())?;
+        let media_type = header
+            .strip_suffix(";base64")
+            .ok_or_else(|| "only base64-encoded data URIs are supported".to_string())?
+            .to_string();
+        let bytes = base64::decode(payload).map_err(|e| e.to_string())?;
+        Ok(ImageSource::Inline { media_type, bytes })
+    }
+
+    /// The media type of this source, guessed from the file extension
+    /// for paths, or taken directly from the `data:` URI.
+    fn media_type(&self) -> String {
+        match self {
+            ImageSource::Path(path) => match path.extension().and_then(|e| e.to_str()) {
+                Some("pdf") => "application/pdf".to_string(),
+                Some(ext) => format!("image/{}", ext.to_ascii_lowercase()),
+                None => "application/octet-stream".to_string(),
+            },
+            ImageSource::Inline { media_type, .. } => media_type.clone(),
+        }
+    }
+
+    /// A lowercase format hint used to pick an image decoder:
+    /// a file extension for paths, or the media type's subtype for inline data.
+    fn format_hint(&self) -> Option<String> {
+        match self {
+            ImageSource::Path(path) => path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.to_ascii_lowercase()),
+            ImageSource::Inline { media_type, .. } => media_type
+                .rsplit('/')
+                .next()
+                .map(|subtype| subtype.to_ascii_lowercase()),
+        }
+    }
+
+    /// Read the full contents of this source into memory.
+    fn into_bytes(self) -> Result<Vec<u8>, String> {
+        match self {
+            ImageSource::Path(path) => std::fs::read(path).map_err(|e| e.to_string()),
+            ImageSource::Inline { bytes, .. } => Ok(bytes),
+        }
+    }
+}
+
+/// A single decoded frame of a (possibly multi-frame) image source.
+struct Frame {
+    width: u32,
+    height: u32,
+    color: image::ColorType,
+    data: Vec<u8>,
+}
+
+impl From<DynamicImage> for Frame {
+    fn from(img: DynamicImage) -> Self {
+        Frame {
+            width: img.width(),
+            height: img.height(),
+            color: img.color(),
+            // Note: not using into_bytes because it panics on 16-bit greyscale images
+            // (https://github.com/image-rs/image/issues/1705)
+            data: img.as_bytes().to_vec(),
+        }
+    }
+}
+
+/// Decode every frame of an animated GIF or multi-page TIFF,
+/// or a single frame for any other image format.
+///
+/// Fails if the frames do not all share the same dimensions and color type,
+/// since a DICOM multi-frame image requires a single common geometry.
+fn load_frames(source: ImageSource) -> Result<Vec<Frame>, String> {
+    let format_hint = source.format_hint();
+    let bytes = source.into_bytes()?;
+
+    let frames: Vec<Frame> = match format_hint.as_deref() {
+        Some("gif") => {
+            let decoder = image::codecs::gif::GifDecoder::new(Cursor::new(&bytes))
+                .map_err(|e| e.to_string())?;
+            decoder
+                .into_frames()
+                .collect_frames()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                // the DICOM Image Pixel module has no alpha channel, so GIF
+                // frames (always RGBA) are flattened to RGB before becoming
+                // a `Frame`, matching the color types the match below knows
+                // how to derive a Photometric Interpretation for.
+                .map(|frame| {
+                    let rgba = DynamicImage::ImageRgba8(frame.into_buffer());
+                    Frame::from(DynamicImage::ImageRgb8(rgba.into_rgb8()))
+                })
+                .collect()
+        }
+        // multi-page TIFF support isn't exposed by the `image` crate's
+        // high-level API, so pages are read with the `tiff` crate directly.
+        Some("tif") | Some("tiff") => {
+            let mut decoder =
+                tiff::decoder::Decoder::new(Cursor::new(&bytes)).map_err(|e| e.to_string())?;
+            let mut frames = Vec::new();
+            loop {
+                let (width, height) = decoder.dimensions().map_err(|e| e.to_string())?;
+                let color = tiff_color_type(decoder.colortype().map_err(|e| e.to_string())?)?;
+                let data = tiff_image_to_bytes(decoder.read_image().map_err(|e| e.to_string())?);
+                frames.push(Frame { width, height, color, data });
+                if !decoder.more_images() {
+                    break;
+                }
+                decoder.next_image().map_err(|e| e.to_string())?;
+            }
+            frames
+        }
+        _ => vec![Frame::from(
+            image::load_from_memory(&bytes).map_err(|e| e.to_string())?,
+        )],
+    };
+
+    let first = &frames[0];
+    if frames
+        .iter()
+        .any(|f| f.width != first.width || f.height != first.height || f.color != first.color)
+    {
+        return Err(
+            "all frames of a multi-frame source must share the same dimensions, \
+             color type, and bit depth"
+                .to_string(),
+        );
+    }
+
+    Ok(frames)
+}
+
+/// Map a `tiff` color type to the `image::ColorType` used elsewhere in this tool.
+fn tiff_color_type(color: tiff::ColorType) -> Result<image::ColorType, String> {
+    match color {
+        tiff::ColorType::Gray(8) => Ok(image::ColorType::L8),
+        tiff::ColorType::Gray(16) => Ok(image::ColorType::L16),
+        tiff::ColorType::RGB(8) => Ok(image::ColorType::Rgb8),
+        tiff::ColorType::RGB(16) => Ok(image::ColorType::Rgb16),
+        other => Err(format!("unsupported TIFF color type {:?}", other)),
+    }
+}
+
+/// Flatten a decoded TIFF page into its raw byte buffer.
+fn tiff_image_to_bytes(image: tiff::decoder::DecodingResult) -> Vec<u8> {
+    match image {
+        tiff::decoder::DecodingResult::U8(v) => v,
+        tiff::decoder::DecodingResult::U16(v) => {
+            v.iter().flat_map(|sample| sample.to_le_bytes()).collect()
+        }
+        _ => unreachable!("tiff_color_type only admits 8- and 16-bit samples"),
+    }
+}
+
 fn report<E: 'static>(err: &E)
 where
     E: std::error::Error,
@@ -85,29 +545,139 @@ fn main() {
     let App {
         dcm_file,
         img_file,
+        doc,
         output,
+        transfer_syntax,
+        patient_name,
+        patient_id,
+        modality,
+        auto_window,
         verbose,
     } = App::parse();
 
     let output = output.unwrap_or_else(|| {
-        let mut path = dcm_file.clone();
+        let mut path = dcm_file
+            .clone()
+            .or_else(|| img_file.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("out"));
         path.set_extension("new.dcm");
         path
     });
 
-    let mut obj = open_file(&dcm_file).unwrap_or_else(|e| {
-        report_with_backtrace(e);
-        std::process::exit(-1);
-    });
+    let mut obj = match &dcm_file {
+        Some(dcm_file) => open_file(dcm_file).unwrap_or_else(|e| {
+            report_with_backtrace(e);
+            std::process::exit(-1);
+        }),
+        None => new_secondary_capture_object(&patient_name, &patient_id, &modality)
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .transfer_syntax("1.2.840.10008.1.2.1")
+                    .media_storage_sop_class_uid(SECONDARY_CAPTURE_SOP_CLASS_UID),
+            )
+            .unwrap_or_else(|e| {
+                report_with_backtrace(e);
+                std::process::exit(-1);
+            }),
+    };
 
-    let img = image::open(img_file).unwrap_or_else(|e| {
-        report(&e);
+    if let Some(doc) = doc {
+        let source = ImageSource::parse(&doc).unwrap_or_else(|e| {
+            eprintln!("[ERROR] {}", e);
+            std::process::exit(-1);
+        });
+        let media_type = source.media_type();
+        let mut bytes = source.into_bytes().unwrap_or_else(|e| {
+            eprintln!("[ERROR] {}", e);
+            std::process::exit(-1);
+        });
+        if bytes.len() % 2 != 0 {
+            bytes.push(0x00);
+        }
+
+        obj.put(DataElement::new(
+            tags::SOP_CLASS_UID,
+            VR::UI,
+            PrimitiveValue::from(ENCAPSULATED_PDF_SOP_CLASS_UID),
+        ));
+        obj.put(DataElement::new(
+            tags::MIME_TYPE_OF_ENCAPSULATED_DOCUMENT,
+            VR::LO,
+            PrimitiveValue::from(media_type.as_str()),
+        ));
+        obj.put(DataElement::new(
+            tags::ENCAPSULATED_DOCUMENT,
+            VR::OB,
+            PrimitiveValue::from(bytes),
+        ));
+        obj.put(DataElement::new(
+            tags::MODALITY,
+            VR::CS,
+            PrimitiveValue::from("DOC"),
+        ));
+        obj.put(DataElement::new(
+            tags::CONVERSION_TYPE,
+            VR::CS,
+            PrimitiveValue::from("WSD"),
+        ));
+
+        // an Encapsulated Document has no Image Pixel module: drop any
+        // image-specific attributes carried over from the base file
+        // (a from-scratch Secondary Capture object has none yet).
+        for tag in [
+            tags::PHOTOMETRIC_INTERPRETATION,
+            tags::PRESENTATION_LUT_SHAPE,
+            tags::SAMPLES_PER_PIXEL,
+            tags::PLANAR_CONFIGURATION,
+            tags::COLUMNS,
+            tags::ROWS,
+            tags::BITS_ALLOCATED,
+            tags::BITS_STORED,
+            tags::HIGH_BIT,
+            tags::PIXEL_REPRESENTATION,
+            tags::NUMBER_OF_FRAMES,
+            tags::PIXEL_DATA,
+        ] {
+            obj.remove_element(tag);
+        }
+
+        let obj = obj
+            .into_inner()
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .transfer_syntax("1.2.840.10008.1.2.1")
+                    .media_storage_sop_class_uid(ENCAPSULATED_PDF_SOP_CLASS_UID),
+            )
+            .unwrap_or_else(|e| {
+                report_with_backtrace(e);
+                std::process::exit(-3);
+            });
+
+        obj.write_to_file(&output).unwrap_or_else(|e| {
+            report_with_backtrace(e);
+            std::process::exit(-4);
+        });
+
+        if verbose {
+            println!("Encapsulated document saved to {}", output.display());
+        }
+        return;
+    }
+
+    let source = ImageSource::parse(&img_file.expect("img_file or doc is required"))
+        .unwrap_or_else(|e| {
+            eprintln!("[ERROR] {}", e);
+            std::process::exit(-1);
+        });
+
+    let frames = load_frames(source).unwrap_or_else(|e| {
+        eprintln!("[ERROR] {}", e);
         std::process::exit(-1);
     });
 
-    let width = img.width();
-    let height = img.height();
-    let color = img.color();
+    let width = frames[0].width;
+    let height = frames[0].height;
+    let color = frames[0].color;
 
     let (pi, spp, bits_stored): (&str, u16, u16) = match color {
         image::ColorType::L8 => ("MONOCHROME2", 1, 8),
@@ -120,15 +690,28 @@ fn main() {
         }
     };
 
-    // Note: not using into_bytes because it panics on 16-bit greyscale images
-    // (https://github.com/image-rs/image/issues/1705)
-    let pixeldata = img.as_bytes();
+    if transfer_syntax == Codec::JpegBaseline && bits_stored != 8 {
+        eprintln!(
+            "[ERROR] JPEG Baseline only supports 8-bit samples, but the source image is {}-bit",
+            bits_stored
+        );
+        std::process::exit(-2);
+    }
 
     if verbose {
-        println!("{}x{} {:?} image", width, height, color);
+        println!(
+            "{}x{} {:?} image, {} frame(s)",
+            width,
+            height,
+            color,
+            frames.len()
+        );
     }
 
     // override attributes at DICOM object
+    let pi = transfer_syntax
+        .photometric_interpretation_override(spp)
+        .unwrap_or(pi);
     obj.put(DataElement::new(
         tags::PHOTOMETRIC_INTERPRETATION,
         VR::CS,
@@ -183,14 +766,21 @@ fn main() {
         PrimitiveValue::from(bits_stored - 1),
     ));
 
+    // this tool never produces signed samples
+    let pixel_representation = 0_u16;
     obj.put(DataElement::new(
         tags::PIXEL_REPRESENTATION,
         VR::US,
-        PrimitiveValue::from(0_u16),
+        PrimitiveValue::from(pixel_representation),
     ));
 
-    for tag in [
+    obj.put(DataElement::new(
         tags::NUMBER_OF_FRAMES,
+        VR::IS,
+        PrimitiveValue::from(frames.len().to_string()),
+    ));
+
+    for tag in [
         tags::PIXEL_ASPECT_RATIO,
         tags::SMALLEST_IMAGE_PIXEL_VALUE,
         tags::LARGEST_IMAGE_PIXEL_VALUE,
@@ -210,11 +800,87 @@ fn main() {
         obj.remove_element(tag);
     }
 
-    obj.put(DataElement::new(
-        tags::PIXEL_DATA,
-        if bits_stored == 8 { VR::OB } else { VR::OW },
-        PrimitiveValue::from(pixeldata),
-    ));
+    if auto_window && spp == 1 {
+        let (min, max) = pixel_value_range(&frames, bits_stored);
+        let center = (min + max) as f64 / 2.0;
+        let width = (max - min + 1) as f64;
+
+        // (0028,0106)/(0028,0107) are always a single 16-bit word,
+        // regardless of BitsAllocated, but its VR (US vs SS) follows
+        // PixelRepresentation, matching how PIXEL_DATA's samples are read.
+        let (pixel_value_vr, smallest, largest) = if pixel_representation == 0 {
+            (
+                VR::US,
+                PrimitiveValue::from(min as u16),
+                PrimitiveValue::from(max as u16),
+            )
+        } else {
+            (
+                VR::SS,
+                PrimitiveValue::from(min as i16),
+                PrimitiveValue::from(max as i16),
+            )
+        };
+
+        obj.put(DataElement::new(
+            tags::SMALLEST_IMAGE_PIXEL_VALUE,
+            pixel_value_vr,
+            smallest,
+        ));
+        obj.put(DataElement::new(
+            tags::LARGEST_IMAGE_PIXEL_VALUE,
+            pixel_value_vr,
+            largest,
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_CENTER,
+            VR::DS,
+            PrimitiveValue::from(center.to_string()),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_WIDTH,
+            VR::DS,
+            PrimitiveValue::from(width.to_string()),
+        ));
+        obj.put(DataElement::new(
+            tags::RESCALE_INTERCEPT,
+            VR::DS,
+            PrimitiveValue::from("0"),
+        ));
+        obj.put(DataElement::new(
+            tags::RESCALE_SLOPE,
+            VR::DS,
+            PrimitiveValue::from("1"),
+        ));
+    }
+
+    match transfer_syntax {
+        Codec::Native => {
+            let pixeldata: Vec<u8> = frames.iter().flat_map(|f| f.data.iter().copied()).collect();
+            obj.put(DataElement::new(
+                tags::PIXEL_DATA,
+                if bits_stored == 8 { VR::OB } else { VR::OW },
+                PrimitiveValue::from(pixeldata),
+            ));
+        }
+        codec => {
+            let fragments: Vec<Vec<u8>> = frames
+                .iter()
+                .map(|frame| {
+                    compress_frame(codec, &frame.data, width, height, color, spp, bits_stored)
+                        .unwrap_or_else(|e| {
+                            eprintln!("[ERROR] {}", e);
+                            std::process::exit(-2);
+                        })
+                })
+                .collect();
+            obj.put(DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                encapsulate(fragments),
+            ));
+        }
+    }
 
     let class_uid = obj.meta().media_storage_sop_class_uid.clone();
 
@@ -222,7 +888,7 @@ fn main() {
         .into_inner()
         .with_meta(
             FileMetaTableBuilder::new()
-                .transfer_syntax("1.2.840.10008.1.2.1")
+                .transfer_syntax(transfer_syntax.transfer_syntax_uid())
                 .media_storage_sop_class_uid(class_uid),
         )
         .unwrap_or_else(|e| {